@@ -0,0 +1,53 @@
+//! Companion CLI for the `service` feature: sends a single command to a
+//! running `libration` instance over its Unix control socket.
+//!
+//! Usage: `libration-ctl <ecc|period|seek|scale|play|pause|query> [value]`
+
+use std::{os::unix::net::UnixStream, process};
+
+use libration::service::{self, Response, WireCommand};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let command = match parse_command(&mut args) {
+        Some(command) => command,
+        None => {
+            eprintln!("usage: libration-ctl <ecc|period|seek|scale|play|pause|query> [value]");
+            process::exit(1);
+        }
+    };
+
+    if let Err(err) = send(command) {
+        eprintln!("libration-ctl: {}", err);
+        process::exit(1);
+    }
+}
+
+fn parse_command(args: &mut impl Iterator<Item = String>) -> Option<WireCommand> {
+    let name = args.next()?;
+    let value = || args.next()?.parse::<f64>().ok();
+
+    Some(match name.as_str() {
+        "ecc" => WireCommand::SetEccentricity(value()?),
+        "period" => WireCommand::SetPeriod(value()?),
+        "seek" => WireCommand::Seek(value()?),
+        "scale" => WireCommand::SetScale(value()?),
+        "play" => WireCommand::Play,
+        "pause" => WireCommand::Pause,
+        "query" => WireCommand::Query,
+        _ => return None,
+    })
+}
+
+fn send(command: WireCommand) -> std::io::Result<()> {
+    let mut stream = UnixStream::connect(service::socket_path())?;
+    service::write_message(&mut stream, &command)?;
+
+    match service::read_message(&mut stream)? {
+        Response::Ack => println!("ok"),
+        Response::State(state) => println!("{:#?}", state),
+        Response::Error(err) => eprintln!("error: {}", err),
+    }
+
+    Ok(())
+}
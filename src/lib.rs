@@ -0,0 +1,2 @@
+#[cfg(feature = "service")]
+pub mod service;
@@ -1,17 +1,31 @@
 use std::{
+    collections::VecDeque,
     f64::consts::PI,
+    fs,
     time::{Duration, Instant},
 };
 
 use iced::{
     canvas::{
         event::Status, path::Builder, Canvas, Cursor, Event, Fill, Frame, Geometry, Path, Program,
-        Stroke,
+        Stroke, Text,
     },
-    executor, keyboard, time, Application, Color, Command, Element, Length, Point, Rectangle,
-    Subscription, Vector,
+    executor, keyboard, mouse, time, Application, Color, Command, Element, Length, Point,
+    Rectangle, Subscription, Vector,
 };
 
+#[cfg(feature = "service")]
+use libration::service;
+#[cfg(feature = "service")]
+use std::{
+    os::unix::net::{UnixListener, UnixStream},
+    sync::mpsc,
+    thread,
+};
+
+const SVG_EXPORT_PATH: &str = "libration.svg";
+const SVG_EXPORT_SIZE: f32 = 600.0;
+
 const EARTH_RADIUS: f32 = 5.0;
 const MOON_RADIUS: f32 = 1.5;
 const MOON_ORBIT_RADIUS: f64 = 40.0;
@@ -38,12 +52,401 @@ const EARTH_MOON_LINE_COLOR: Color = Color {
     a: 1.0,
 };
 
-#[derive(Debug, Clone, Copy)]
+const HUD_TEXT_COLOR: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 1.0,
+    a: 1.0,
+};
+const HUD_LINE_HEIGHT: f32 = 14.0;
+const HUD_FONT_SIZE: f32 = 12.0;
+
+const ORBIT_TICK_COLOR: Color = Color {
+    r: 0.6,
+    g: 0.6,
+    b: 0.4,
+    a: 1.0,
+};
+const ORBIT_TICK_LEN: f64 = 2.0;
+const ORBIT_TICK_LABEL_OFFSET: f64 = 6.0;
+const ORBIT_TICK_FONT_SIZE: f32 = 3.0;
+
+const ZOOM_STEP: f64 = 1.1;
+const SCRUB_EDGE_HEIGHT: f32 = 20.0;
+
+/// Size of one fixed simulation step, as a fraction of a full period. The
+/// animation always advances in steps of this size, regardless of how
+/// often `Message::Tick` arrives, so recordings are reproducible across
+/// machines.
+const STEP_FRACTION: f64 = 1.0 / 240.0;
+const MIN_SPEEDUP: i32 = 1;
+const MAX_SPEEDUP: i32 = 64;
+
+/// Smallest period we'll accept. `step_seconds` below is derived from
+/// `period`, so a zero or negative value would make the tick loop spin
+/// forever; every setter clamps to this floor instead.
+const MIN_PERIOD: f64 = 1e-3;
+
+/// Smallest scale we'll accept. `draw_scene` divides by `scale` to get
+/// pixels per unit, so a zero or negative value would produce a
+/// non-finite transform; the setters that take an arbitrary value (console
+/// and control socket) clamp to this floor instead. Keyboard and wheel zoom
+/// only multiply or divide by a fixed factor and are not clamped.
+const MIN_SCALE: f64 = 1e-3;
+
+const TILT_STEP: f64 = 0.02;
+const MAX_TILT: f64 = 0.5;
+
+const CROSSHAIR_SIZE: f32 = 1.0;
+const CROSSHAIR_COLOR: Color = Color {
+    r: 1.0,
+    g: 1.0,
+    b: 0.0,
+    a: 1.0,
+};
+
+const TRAIL_MAX_POINTS: usize = 480;
+const TRAIL_BOX_SIZE: f32 = 80.0;
+const TRAIL_MARGIN: f32 = 10.0;
+const TRAIL_SCALE: f32 = 3.0;
+const TRAIL_COLOR: Color = Color {
+    r: 1.0,
+    g: 0.8,
+    b: 0.2,
+    a: 1.0,
+};
+
+/// A drawing back-end able to render the handful of primitives the scene is
+/// built out of. Implemented once for the `iced` `Frame` used on screen and
+/// once for `SvgScene` so the geometry in `Libration`'s `draw_*` helpers only
+/// has to be written down a single time.
+trait Scene {
+    fn circle(&mut self, center: Point, radius: f32, color: Color, filled: bool);
+    fn line(&mut self, from: Point, to: Point, color: Color);
+    fn path(&mut self, points: &[Point], color: Color, filled: bool);
+    fn text(&mut self, position: Point, content: String, color: Color, size: f32);
+
+    /// Runs `draw` with an additional translate/scale/rotate transform
+    /// pushed on top of the current one, restoring it afterwards.
+    fn with_transform(
+        &mut self,
+        translate: Vector,
+        scale: f32,
+        rotate: f32,
+        draw: impl FnOnce(&mut Self),
+    );
+}
+
+impl Scene for Frame {
+    fn circle(&mut self, center: Point, radius: f32, color: Color, filled: bool) {
+        let circle = Path::circle(center, radius);
+        if filled {
+            self.fill(
+                &circle,
+                Fill {
+                    color,
+                    ..Default::default()
+                },
+            );
+        } else {
+            self.stroke(&circle, Stroke::default().with_color(color));
+        }
+    }
+
+    fn line(&mut self, from: Point, to: Point, color: Color) {
+        let path = Path::line(from, to);
+        self.stroke(&path, Stroke::default().with_color(color));
+    }
+
+    fn path(&mut self, points: &[Point], color: Color, filled: bool) {
+        if points.is_empty() {
+            return;
+        }
+        let mut builder = Builder::new();
+        builder.move_to(points[0]);
+        for point in &points[1..] {
+            builder.line_to(*point);
+        }
+        let path = builder.build();
+        if filled {
+            self.fill(
+                &path,
+                Fill {
+                    color,
+                    ..Default::default()
+                },
+            );
+        } else {
+            self.stroke(&path, Stroke::default().with_color(color));
+        }
+    }
+
+    fn with_transform(
+        &mut self,
+        translate: Vector,
+        scale: f32,
+        rotate: f32,
+        draw: impl FnOnce(&mut Self),
+    ) {
+        self.with_save(|frame| {
+            frame.translate(translate);
+            frame.scale(scale);
+            frame.rotate(rotate);
+            draw(frame);
+        });
+    }
+
+    fn text(&mut self, position: Point, content: String, color: Color, size: f32) {
+        self.fill_text(Text {
+            content,
+            position,
+            color,
+            size,
+            ..Text::default()
+        });
+    }
+}
+
+/// An SVG document builder that mirrors the primitives of [`Scene`], so the
+/// same scene code can be serialized to a standalone `.svg` file.
+struct SvgScene {
+    width: f32,
+    height: f32,
+    body: String,
+}
+
+impl SvgScene {
+    fn new(width: f32, height: f32) -> Self {
+        SvgScene {
+            width,
+            height,
+            body: String::new(),
+        }
+    }
+
+    fn into_document(self) -> String {
+        format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="0 0 {} {}">{}</svg>"#,
+            self.width, self.height, self.width, self.height, self.body
+        )
+    }
+}
+
+fn to_svg_color(color: Color) -> String {
+    let to_byte = |c: f32| (c * 255.0).round() as u8;
+    format!(
+        "rgba({},{},{},{})",
+        to_byte(color.r),
+        to_byte(color.g),
+        to_byte(color.b),
+        color.a
+    )
+}
+
+impl Scene for SvgScene {
+    fn circle(&mut self, center: Point, radius: f32, color: Color, filled: bool) {
+        let style = if filled {
+            format!("fill:{};stroke:none", to_svg_color(color))
+        } else {
+            format!("fill:none;stroke:{}", to_svg_color(color))
+        };
+        self.body.push_str(&format!(
+            r#"<circle cx="{}" cy="{}" r="{}" style="{}"/>"#,
+            center.x, center.y, radius, style
+        ));
+    }
+
+    fn line(&mut self, from: Point, to: Point, color: Color) {
+        self.body.push_str(&format!(
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" style="stroke:{}"/>"#,
+            from.x,
+            from.y,
+            to.x,
+            to.y,
+            to_svg_color(color)
+        ));
+    }
+
+    fn path(&mut self, points: &[Point], color: Color, filled: bool) {
+        if points.is_empty() {
+            return;
+        }
+        let mut d = format!("M {} {}", points[0].x, points[0].y);
+        for point in &points[1..] {
+            d.push_str(&format!(" L {} {}", point.x, point.y));
+        }
+        if filled {
+            d.push_str(" Z");
+        }
+        let style = if filled {
+            format!("fill:{};stroke:none", to_svg_color(color))
+        } else {
+            format!("fill:none;stroke:{}", to_svg_color(color))
+        };
+        self.body
+            .push_str(&format!(r#"<path d="{}" style="{}"/>"#, d, style));
+    }
+
+    fn with_transform(
+        &mut self,
+        translate: Vector,
+        scale: f32,
+        rotate: f32,
+        draw: impl FnOnce(&mut Self),
+    ) {
+        self.body.push_str(&format!(
+            r#"<g transform="translate({},{}) scale({}) rotate({})">"#,
+            translate.x,
+            translate.y,
+            scale,
+            rotate.to_degrees()
+        ));
+        draw(self);
+        self.body.push_str("</g>");
+    }
+
+    fn text(&mut self, position: Point, content: String, color: Color, size: f32) {
+        self.body.push_str(&format!(
+            r#"<text x="{}" y="{}" font-size="{}" fill="{}">{}</text>"#,
+            position.x,
+            position.y,
+            size,
+            to_svg_color(color),
+            content
+        ));
+    }
+}
+
+/// A [`service::WireCommand`] after it has crossed the socket, ready to be
+/// applied to `Libration`. `Query` carries a reply channel instead of being
+/// serializable, since the answer has to come from live application state.
+#[cfg(feature = "service")]
+#[derive(Debug, Clone)]
+enum ServiceCommand {
+    SetEccentricity(f64),
+    SetPeriod(f64),
+    Play,
+    Pause,
+    Seek(f64),
+    SetScale(f64),
+    Query(mpsc::SyncSender<service::StateSnapshot>),
+}
+
+#[cfg(feature = "service")]
+impl ServiceCommand {
+    fn from_wire(wire: service::WireCommand) -> Option<Self> {
+        Some(match wire {
+            service::WireCommand::SetEccentricity(v) => ServiceCommand::SetEccentricity(v),
+            service::WireCommand::SetPeriod(v) => ServiceCommand::SetPeriod(v),
+            service::WireCommand::Play => ServiceCommand::Play,
+            service::WireCommand::Pause => ServiceCommand::Pause,
+            service::WireCommand::Seek(v) => ServiceCommand::Seek(v),
+            service::WireCommand::SetScale(v) => ServiceCommand::SetScale(v),
+            service::WireCommand::Query => return None,
+        })
+    }
+}
+
+/// Recipe bridging the control socket's background thread into an iced
+/// `Subscription`, the same way `time::every` bridges a timer.
+#[cfg(feature = "service")]
+struct ControlSocket;
+
+#[cfg(feature = "service")]
+impl<H, I> iced_native::subscription::Recipe<H, I> for ControlSocket
+where
+    H: std::hash::Hasher,
+{
+    type Output = ServiceCommand;
+
+    fn hash(&self, state: &mut H) {
+        use std::hash::Hash;
+        std::any::TypeId::of::<Self>().hash(state);
+    }
+
+    fn stream(
+        self: Box<Self>,
+        _input: futures::stream::BoxStream<'static, I>,
+    ) -> futures::stream::BoxStream<'static, Self::Output> {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+
+        thread::spawn(move || {
+            let path = service::socket_path();
+            let _ = fs::remove_file(&path);
+
+            let listener = match UnixListener::bind(&path) {
+                Ok(listener) => listener,
+                Err(err) => {
+                    eprintln!("service: failed to bind {}: {}", path.display(), err);
+                    return;
+                }
+            };
+
+            for stream in listener.incoming().flatten() {
+                let sender = sender.clone();
+                thread::spawn(move || handle_connection(stream, sender));
+            }
+        });
+
+        Box::pin(receiver)
+    }
+}
+
+#[cfg(feature = "service")]
+fn handle_connection(
+    mut stream: UnixStream,
+    sender: futures::channel::mpsc::UnboundedSender<ServiceCommand>,
+) {
+    loop {
+        let wire: service::WireCommand = match service::read_message(&mut stream) {
+            Ok(wire) => wire,
+            Err(_) => return,
+        };
+
+        if wire == service::WireCommand::Query {
+            let (reply, answer) = mpsc::sync_channel(1);
+            if sender.unbounded_send(ServiceCommand::Query(reply)).is_err() {
+                return;
+            }
+            let response = match answer.recv() {
+                Ok(state) => service::Response::State(state),
+                Err(err) => service::Response::Error(err.to_string()),
+            };
+            if service::write_message(&mut stream, &response).is_err() {
+                return;
+            }
+            continue;
+        }
+
+        let response = match ServiceCommand::from_wire(wire) {
+            Some(command) if sender.unbounded_send(command).is_ok() => service::Response::Ack,
+            _ => service::Response::Error("control channel closed".to_string()),
+        };
+        if service::write_message(&mut stream, &response).is_err() {
+            return;
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 enum Message {
     Tick,
+    ExportSvg,
+    StepForward,
+    StepBackward,
+    Command(String),
+    #[cfg(feature = "service")]
+    Control(ServiceCommand),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DragMode {
+    None,
+    Pan,
+    Scrub,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 struct Libration {
     playing: bool,
     scale: f64,
@@ -52,6 +455,25 @@ struct Libration {
     eccentricity: f64,
     last_tick: Option<Instant>,
     center_moon: bool,
+    max_libration_deg: f64,
+    pan: Vector,
+    drag_mode: DragMode,
+    last_cursor: Option<Point>,
+    accumulator: f64,
+    speedup: i32,
+    console_active: bool,
+    console_input: String,
+    console_history: Vec<String>,
+    console_history_cursor: Option<usize>,
+    /// `(time_fraction, eccentricity)` pairs set via the `keyframe` console
+    /// command, sorted by `time_fraction`, interpolated across a cycle.
+    keyframes: Vec<(f64, f64)>,
+    /// Axial tilt (radians): the amplitude of the Moon's north-south nod.
+    tilt: f64,
+    trail_enabled: bool,
+    /// Accumulated `(libration_lon_deg, libration_lat_deg)` sub-observer
+    /// points, traced out as a small inset plot while `trail_enabled`.
+    trail: VecDeque<(f64, f64)>,
 }
 
 impl Application for Libration {
@@ -69,6 +491,20 @@ impl Application for Libration {
                 eccentricity: 0.0,
                 last_tick: None,
                 center_moon: false,
+                max_libration_deg: 0.0,
+                pan: Vector::new(0.0, 0.0),
+                drag_mode: DragMode::None,
+                last_cursor: None,
+                accumulator: 0.0,
+                speedup: 1,
+                console_active: false,
+                console_input: String::new(),
+                console_history: Vec::new(),
+                console_history_cursor: None,
+                keyframes: Vec::new(),
+                tilt: 0.0,
+                trail_enabled: false,
+                trail: VecDeque::new(),
             },
             Command::none(),
         )
@@ -84,13 +520,54 @@ impl Application for Libration {
                 let now = Instant::now();
                 if let Some(last_tick) = self.last_tick {
                     let time_diff = ((now - last_tick).as_millis() as f64) / 1000.0;
-                    self.time += time_diff / self.period;
-                    while self.time > 1.0 {
-                        self.time -= 1.0;
-                    }
+                    self.accumulator += time_diff * self.speedup as f64;
                 }
                 self.last_tick = Some(now);
+
+                // Floored defensively: `period` is clamped at the setters,
+                // but this keeps the loop from spinning forever even if
+                // that invariant is ever broken.
+                let step_seconds = (self.period * STEP_FRACTION).max(MIN_PERIOD * STEP_FRACTION);
+                while self.accumulator >= step_seconds {
+                    self.step(STEP_FRACTION);
+                    self.accumulator -= step_seconds;
+                }
+            }
+            Message::StepForward if !self.playing => self.step(STEP_FRACTION),
+            Message::StepBackward if !self.playing => self.step(-STEP_FRACTION),
+            Message::Command(input) => {
+                self.apply_command(&input);
+                self.console_history.push(input);
+            }
+            Message::ExportSvg => {
+                let mut scene = SvgScene::new(SVG_EXPORT_SIZE, SVG_EXPORT_SIZE);
+                self.draw_scene(&mut scene, SVG_EXPORT_SIZE, SVG_EXPORT_SIZE);
+                if let Err(err) = fs::write(SVG_EXPORT_PATH, scene.into_document()) {
+                    eprintln!("failed to write {}: {}", SVG_EXPORT_PATH, err);
+                }
             }
+            #[cfg(feature = "service")]
+            Message::Control(command) => match command {
+                ServiceCommand::SetEccentricity(v) => self.eccentricity = v.clamp(0.0, 0.99),
+                ServiceCommand::SetPeriod(v) => self.period = v.max(MIN_PERIOD),
+                ServiceCommand::Play => self.playing = true,
+                ServiceCommand::Pause => {
+                    self.playing = false;
+                    self.last_tick = None;
+                    self.accumulator = 0.0;
+                }
+                ServiceCommand::Seek(v) => self.set_time(v.clamp(0.0, 1.0)),
+                ServiceCommand::SetScale(v) => self.scale = v.max(MIN_SCALE),
+                ServiceCommand::Query(reply) => {
+                    let _ = reply.send(service::StateSnapshot {
+                        playing: self.playing,
+                        scale: self.scale,
+                        time: self.time,
+                        period: self.period,
+                        eccentricity: self.eccentricity,
+                    });
+                }
+            },
             _ => (),
         }
         Command::none()
@@ -104,53 +581,93 @@ impl Application for Libration {
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        time::every(Duration::from_millis(30)).map(|_| Message::Tick)
+        let tick = time::every(Duration::from_millis(30)).map(|_| Message::Tick);
+
+        #[cfg(feature = "service")]
+        let tick = Subscription::batch(vec![
+            tick,
+            Subscription::from_recipe(ControlSocket).map(Message::Control),
+        ]);
+
+        tick
     }
 }
 
 impl Program<Message> for Libration {
     fn draw(&self, bounds: Rectangle<f32>, _cursor: Cursor) -> Vec<Geometry> {
         let mut frame = Frame::new(bounds.size());
-
-        let smaller_dim = if bounds.size().width < bounds.size().height {
-            bounds.size().width
-        } else {
-            bounds.size().height
-        };
-
-        frame.translate(frame.center() - Point::new(0.0, 0.0));
-        frame.scale(smaller_dim / self.scale as f32);
-
-        if self.center_moon {
-            let (x, y) = self.moon_pos();
-            frame.translate(Vector::new(-x, -y));
-        }
-
-        self.draw_earth_moon_line(&mut frame);
-
-        let earth = Path::circle(Point::new(0.0, 0.0), EARTH_RADIUS);
-        frame.fill(
-            &earth,
-            Fill {
-                color: Color::new(0.0, 1.0, 1.0, 1.0),
-                ..Default::default()
-            },
-        );
-
-        self.draw_moon_orbit(&mut frame);
-
-        self.draw_moon(&mut frame);
-
+        self.draw_scene(&mut frame, bounds.size().width, bounds.size().height);
         vec![frame.into_geometry()]
     }
 
     fn update(
         &mut self,
         event: Event,
-        _bounds: Rectangle<f32>,
-        _cursor: Cursor,
+        bounds: Rectangle<f32>,
+        cursor: Cursor,
     ) -> (Status, Option<Message>) {
+        if self.console_active {
+            return self.update_console(event);
+        }
+
         match event {
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::Semicolon,
+                ..
+            }) => {
+                self.console_active = true;
+                self.console_input.clear();
+                self.console_history_cursor = None;
+                return (Status::Captured, None);
+            }
+            Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
+                if let Some(position) = cursor.position_in(&bounds) {
+                    let delta_y = match delta {
+                        mouse::ScrollDelta::Lines { y, .. } => y,
+                        mouse::ScrollDelta::Pixels { y, .. } => y,
+                    };
+                    self.zoom_around(position, bounds, delta_y);
+                    return (Status::Captured, None);
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                if let Some(position) = cursor.position_in(&bounds) {
+                    self.drag_mode =
+                        if !self.playing && position.y > bounds.height - SCRUB_EDGE_HEIGHT {
+                            self.scrub_to(position, bounds);
+                            DragMode::Scrub
+                        } else {
+                            DragMode::Pan
+                        };
+                    self.last_cursor = Some(position);
+                    return (Status::Captured, None);
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                self.drag_mode = DragMode::None;
+                self.last_cursor = None;
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                if self.drag_mode == DragMode::None {
+                    return (Status::Ignored, None);
+                }
+                if let Some(position) = cursor.position_in(&bounds) {
+                    match self.drag_mode {
+                        DragMode::Pan => {
+                            if let Some(last_cursor) = self.last_cursor {
+                                let smaller_dim = bounds.width.min(bounds.height);
+                                let px_per_unit = smaller_dim / self.scale as f32;
+                                self.pan.x += (position.x - last_cursor.x) / px_per_unit;
+                                self.pan.y += (position.y - last_cursor.y) / px_per_unit;
+                            }
+                        }
+                        DragMode::Scrub => self.scrub_to(position, bounds),
+                        DragMode::None => (),
+                    }
+                    self.last_cursor = Some(position);
+                    return (Status::Captured, None);
+                }
+            }
             Event::Keyboard(keyboard::Event::KeyPressed {
                 key_code: keyboard::KeyCode::Space,
                 ..
@@ -158,8 +675,33 @@ impl Program<Message> for Libration {
                 self.playing = !self.playing;
                 if !self.playing {
                     self.last_tick = None;
+                    self.accumulator = 0.0;
                 }
             }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::Right,
+                ..
+            }) => {
+                return (Status::Captured, Some(Message::StepForward));
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::Left,
+                ..
+            }) => {
+                return (Status::Captured, Some(Message::StepBackward));
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::Up,
+                ..
+            }) => {
+                self.speedup = (self.speedup * 2).min(MAX_SPEEDUP);
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::Down,
+                ..
+            }) => {
+                self.speedup = (self.speedup / 2).max(MIN_SPEEDUP);
+            }
             Event::Keyboard(keyboard::Event::KeyPressed {
                 key_code: keyboard::KeyCode::E,
                 ..
@@ -196,6 +738,30 @@ impl Program<Message> for Libration {
             }) => {
                 self.center_moon = !self.center_moon;
             }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::S,
+                ..
+            }) => {
+                return (Status::Captured, Some(Message::ExportSvg));
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::I,
+                ..
+            }) => {
+                self.tilt = (self.tilt + TILT_STEP).min(MAX_TILT);
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::K,
+                ..
+            }) => {
+                self.tilt = (self.tilt - TILT_STEP).max(0.0);
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::T,
+                ..
+            }) => {
+                self.trail_enabled = !self.trail_enabled;
+            }
             _ => (),
         }
         (Status::Ignored, None)
@@ -203,6 +769,224 @@ impl Program<Message> for Libration {
 }
 
 impl Libration {
+    /// Adjusts `scale` by one zoom step and corrects `pan` so that the world
+    /// point currently under `cursor` stays fixed on screen.
+    fn zoom_around(&mut self, cursor: Point, bounds: Rectangle<f32>, delta_y: f32) {
+        let smaller_dim = bounds.width.min(bounds.height);
+        let center = Point::new(bounds.width / 2.0, bounds.height / 2.0);
+
+        let old_px_per_unit = smaller_dim / self.scale as f32;
+        let cursor_world_x = (cursor.x - center.x) / old_px_per_unit - self.pan.x;
+        let cursor_world_y = (cursor.y - center.y) / old_px_per_unit - self.pan.y;
+
+        if delta_y > 0.0 {
+            self.scale /= ZOOM_STEP;
+        } else if delta_y < 0.0 {
+            self.scale *= ZOOM_STEP;
+        }
+
+        let new_px_per_unit = smaller_dim / self.scale as f32;
+        self.pan.x = (cursor.x - center.x) / new_px_per_unit - cursor_world_x;
+        self.pan.y = (cursor.y - center.y) / new_px_per_unit - cursor_world_y;
+    }
+
+    /// Parks `time` at the fraction of the bottom edge the cursor is over,
+    /// used while scrubbing.
+    fn scrub_to(&mut self, cursor: Point, bounds: Rectangle<f32>) {
+        self.set_time(((cursor.x / bounds.width) as f64).clamp(0.0, 1.0));
+    }
+
+    /// Jumps `time` directly to `time`, re-deriving `eccentricity` from any
+    /// keyframes defined there so a seek lands exactly like an incremental
+    /// `step` would, instead of leaving it stuck at its pre-jump value.
+    fn set_time(&mut self, time: f64) {
+        self.time = time;
+        if let Some(eccentricity) = self.interpolate_keyframes() {
+            self.eccentricity = eccentricity;
+        }
+    }
+
+    /// Advances (or rewinds, for negative `delta`) the simulation by one
+    /// fixed step's worth of cycle fraction, wrapping `time` into `[0, 1)`
+    /// and keeping `max_libration_deg` in sync.
+    fn step(&mut self, delta: f64) {
+        let mut time = self.time + delta;
+        while time >= 1.0 {
+            time -= 1.0;
+            self.max_libration_deg = 0.0;
+        }
+        while time < 0.0 {
+            time += 1.0;
+            self.max_libration_deg = 0.0;
+        }
+        self.set_time(time);
+
+        let libration_deg = self.libration_angle().to_degrees().abs();
+        self.max_libration_deg = self.max_libration_deg.max(libration_deg);
+
+        if self.trail_enabled {
+            self.trail.push_back((
+                self.libration_angle().to_degrees(),
+                self.sub_observer_latitude().to_degrees(),
+            ));
+            if self.trail.len() > TRAIL_MAX_POINTS {
+                self.trail.pop_front();
+            }
+        }
+    }
+
+    /// Eccentricity interpolated from the `keyframes` list at the current
+    /// `time`, wrapping from the last keyframe back to the first across the
+    /// cycle boundary. Returns `None` when there are no keyframes set.
+    fn interpolate_keyframes(&self) -> Option<f64> {
+        if self.keyframes.is_empty() {
+            return None;
+        }
+        if self.keyframes.len() == 1 {
+            return Some(self.keyframes[0].1);
+        }
+
+        for window in self.keyframes.windows(2) {
+            let (t0, e0) = window[0];
+            let (t1, e1) = window[1];
+            if self.time >= t0 && self.time <= t1 {
+                let frac = (self.time - t0) / (t1 - t0);
+                return Some(e0 + (e1 - e0) * frac);
+            }
+        }
+
+        let (t_last, e_last) = *self.keyframes.last().unwrap();
+        let (t_first, e_first) = self.keyframes[0];
+        let span = 1.0 - t_last + t_first;
+        let local = if self.time >= t_last {
+            self.time - t_last
+        } else {
+            self.time + 1.0 - t_last
+        };
+        let frac = if span > 0.0 { local / span } else { 0.0 };
+        Some(e_last + (e_first - e_last) * frac)
+    }
+
+    /// Handles keyboard input while the `:`-console is open, capturing text
+    /// into `console_input` instead of letting it fall through to the
+    /// regular keybindings.
+    fn update_console(&mut self, event: Event) -> (Status, Option<Message>) {
+        match event {
+            Event::Keyboard(keyboard::Event::CharacterReceived(c)) if !c.is_control() => {
+                self.console_input.push(c);
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::Enter,
+                ..
+            }) => {
+                let input = std::mem::take(&mut self.console_input);
+                self.console_active = false;
+                return (Status::Captured, Some(Message::Command(input)));
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::Escape,
+                ..
+            }) => {
+                self.console_input.clear();
+                self.console_active = false;
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::Backspace,
+                ..
+            }) => {
+                self.console_input.pop();
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::Up,
+                ..
+            }) => {
+                if !self.console_history.is_empty() {
+                    let next = self
+                        .console_history_cursor
+                        .map_or(self.console_history.len() - 1, |i| i.saturating_sub(1));
+                    self.console_history_cursor = Some(next);
+                    self.console_input = self.console_history[next].clone();
+                }
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code: keyboard::KeyCode::Down,
+                ..
+            }) => {
+                if let Some(i) = self.console_history_cursor {
+                    if i + 1 < self.console_history.len() {
+                        self.console_history_cursor = Some(i + 1);
+                        self.console_input = self.console_history[i + 1].clone();
+                    } else {
+                        self.console_history_cursor = None;
+                        self.console_input.clear();
+                    }
+                }
+            }
+            _ => (),
+        }
+        (Status::Captured, None)
+    }
+
+    /// Adds (or replaces, if one already sits at `time_fraction`) a
+    /// keyframe, keeping the list sorted by `time_fraction`.
+    fn add_keyframe(&mut self, time_fraction: f64, eccentricity: f64) {
+        self.keyframes
+            .retain(|(t, _)| (*t - time_fraction).abs() > 1e-9);
+        self.keyframes.push((time_fraction, eccentricity));
+        self.keyframes
+            .sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap());
+    }
+
+    /// Parses and applies one console command line, e.g. `ecc 0.0549`,
+    /// `period 27.32`, `seek 0.5`, `scale 120`, or `keyframe 0.25 0.2`.
+    /// Each argument is run through [`eval_expr`] so users can type simple
+    /// arithmetic instead of only bare literals.
+    fn apply_command(&mut self, input: &str) {
+        let mut parts = input.trim().splitn(2, char::is_whitespace);
+        let name = match parts.next() {
+            Some(name) if !name.is_empty() => name,
+            _ => return,
+        };
+        let rest = parts.next().unwrap_or("").trim();
+
+        match name {
+            "ecc" => {
+                if let Some(value) = eval_expr(rest).filter(|v| v.is_finite()) {
+                    self.eccentricity = value.clamp(0.0, 0.99);
+                }
+            }
+            "period" => {
+                if let Some(value) = eval_expr(rest).filter(|v| v.is_finite()) {
+                    self.period = value.max(MIN_PERIOD);
+                }
+            }
+            "seek" => {
+                if let Some(value) = eval_expr(rest).filter(|v| v.is_finite()) {
+                    self.set_time(value.clamp(0.0, 1.0));
+                }
+            }
+            "scale" => {
+                if let Some(value) = eval_expr(rest).filter(|v| v.is_finite()) {
+                    self.scale = value.max(MIN_SCALE);
+                }
+            }
+            "keyframe" => {
+                let mut args = rest.splitn(2, char::is_whitespace);
+                let time_fraction = args.next().and_then(eval_expr).filter(|v| v.is_finite());
+                let eccentricity = args
+                    .next()
+                    .map(str::trim)
+                    .and_then(eval_expr)
+                    .filter(|v| v.is_finite());
+                if let (Some(time_fraction), Some(eccentricity)) = (time_fraction, eccentricity) {
+                    self.add_keyframe(time_fraction.clamp(0.0, 1.0), eccentricity.clamp(0.0, 0.99));
+                }
+            }
+            "clearkeyframes" => self.keyframes.clear(),
+            _ => eprintln!("console: unknown command {:?}", name),
+        }
+    }
+
     fn r(&self, p: f64, phi: f64) -> f64 {
         p / (1.0 + self.eccentricity * phi.cos())
     }
@@ -211,13 +995,22 @@ impl Libration {
         ((-r * phi.cos()) as f32, (r * phi.sin()) as f32)
     }
 
-    fn moon_pos(&self) -> (f32, f32) {
+    /// The mean anomaly, wrapped into `(-PI, PI]` the same way `moon_pos`
+    /// wraps it before solving Kepler's equation.
+    fn mean_anomaly(&self) -> f64 {
         let mut mean_anomaly = self.time * 2.0 * PI;
-        let ecc = self.eccentricity;
-
         if mean_anomaly > PI {
             mean_anomaly -= 2.0 * PI;
         }
+        mean_anomaly
+    }
+
+    /// Returns the Moon's screen-space `(x, y)` position together with its
+    /// true anomaly, so callers (like the HUD) can compare it against the
+    /// uniformly-advancing mean anomaly to get the libration angle.
+    fn moon_pos(&self) -> (f32, f32, f64) {
+        let mean_anomaly = self.mean_anomaly();
+        let ecc = self.eccentricity;
 
         let f = |ecc_anom: f64| ecc_anom - ecc * ecc_anom.sin() - mean_anomaly;
         let df = |ecc_anom: f64| 1.0 - ecc * ecc_anom.cos();
@@ -230,70 +1023,337 @@ impl Libration {
         let true_anom = ((1.0 - ecc * ecc).sqrt() * ecc_anom.sin()).atan2(ecc_anom.cos() - ecc);
 
         let r = self.r(MOON_ORBIT_RADIUS, true_anom);
-        Self::rphi_to_xy(r, true_anom)
+        let (x, y) = Self::rphi_to_xy(r, true_anom);
+        (x, y, true_anom)
+    }
+
+    /// The optical libration in longitude: the difference between the true
+    /// anomaly (where the Moon actually is) and the mean anomaly (where the
+    /// rotation indicator, advancing uniformly, points), in radians.
+    fn libration_angle(&self) -> f64 {
+        let (_, _, true_anom) = self.moon_pos();
+        true_anom - self.mean_anomaly()
+    }
+
+    /// The optical libration in latitude: the sub-observer latitude, in
+    /// radians, found by projecting the axial tilt through the current
+    /// orbital phase (the "second phase" alongside the true anomaly that
+    /// drives the longitude wobble).
+    fn sub_observer_latitude(&self) -> f64 {
+        self.tilt * (self.time * 2.0 * PI).sin()
+    }
+
+    /// Draws the whole scene (Earth, orbit, Earth-Moon line, Moon and its
+    /// rotation indicator) onto `scene`, applying the same world->screen
+    /// transform regardless of back-end: centering in the `width` x `height` area,
+    /// scaling by `smaller_dim / self.scale`, and the optional
+    /// `center_moon` translation.
+    fn draw_scene(&self, scene: &mut impl Scene, width: f32, height: f32) {
+        self.draw_hud(scene);
+        self.draw_console(scene, height);
+        self.draw_trail(scene, width);
+
+        let smaller_dim = if width < height { width } else { height };
+        let center = Point::new(width / 2.0, height / 2.0);
+
+        scene.with_transform(
+            Vector::new(center.x, center.y),
+            smaller_dim / self.scale as f32,
+            0.0,
+            |scene| {
+                let center_moon_offset = if self.center_moon {
+                    let (x, y, _) = self.moon_pos();
+                    Vector::new(-x, -y)
+                } else {
+                    Vector::new(0.0, 0.0)
+                };
+                let offset = Vector::new(
+                    center_moon_offset.x + self.pan.x,
+                    center_moon_offset.y + self.pan.y,
+                );
+
+                scene.with_transform(offset, 1.0, 0.0, |scene| {
+                    self.draw_earth_moon_line(scene);
+
+                    scene.circle(
+                        Point::new(0.0, 0.0),
+                        EARTH_RADIUS,
+                        Color::new(0.0, 1.0, 1.0, 1.0),
+                        true,
+                    );
+
+                    self.draw_moon_orbit(scene);
+                    self.draw_orbit_ticks(scene);
+                    self.draw_moon(scene);
+                });
+            },
+        );
+    }
+
+    /// Top-left text overlay reporting the live orbital quantities, drawn in
+    /// plain screen space (outside the world->screen transform) so it stays
+    /// put regardless of zoom or `center_moon`.
+    fn draw_hud(&self, scene: &mut impl Scene) {
+        let (_, _, true_anom) = self.moon_pos();
+        let mean_anom = self.mean_anomaly();
+        let libration_deg = self.libration_angle().to_degrees();
+
+        let lines = [
+            format!("period: {:.2}", self.period),
+            format!("speed: {}x", self.speedup),
+            format!("eccentricity: {:.2}", self.eccentricity),
+            format!("mean anomaly: {:.1} deg", mean_anom.to_degrees()),
+            format!("true anomaly: {:.1} deg", true_anom.to_degrees()),
+            format!("libration in longitude: {:.2} deg", libration_deg),
+            format!(
+                "max libration this cycle: {:.2} deg",
+                self.max_libration_deg
+            ),
+            format!(
+                "libration in latitude: {:.2} deg",
+                self.sub_observer_latitude().to_degrees()
+            ),
+        ];
+
+        for (i, line) in lines.iter().enumerate() {
+            scene.text(
+                Point::new(10.0, 16.0 + i as f32 * HUD_LINE_HEIGHT),
+                line.clone(),
+                HUD_TEXT_COLOR,
+                HUD_FONT_SIZE,
+            );
+        }
+    }
+
+    /// Bottom-left `:`-prompt shown while the command console is open.
+    fn draw_console(&self, scene: &mut impl Scene, height: f32) {
+        if !self.console_active {
+            return;
+        }
+        scene.text(
+            Point::new(10.0, height - 10.0),
+            format!(":{}", self.console_input),
+            HUD_TEXT_COLOR,
+            HUD_FONT_SIZE,
+        );
     }
 
-    fn draw_moon_orbit(&self, frame: &mut Frame) {
+    /// Top-right inset plot of the recent `(longitude, latitude)` libration
+    /// trail, in plain screen space like the HUD. Drawn only while
+    /// `trail_enabled` and there are at least two points to connect.
+    fn draw_trail(&self, scene: &mut impl Scene, width: f32) {
+        if !self.trail_enabled || self.trail.len() < 2 {
+            return;
+        }
+
+        let origin = Point::new(
+            width - TRAIL_BOX_SIZE - TRAIL_MARGIN,
+            TRAIL_MARGIN + TRAIL_BOX_SIZE,
+        );
+        let center = Point::new(
+            origin.x + TRAIL_BOX_SIZE / 2.0,
+            origin.y - TRAIL_BOX_SIZE / 2.0,
+        );
+
+        let points: Vec<Point> = self
+            .trail
+            .iter()
+            .map(|(lon, lat)| {
+                Point::new(
+                    center.x + *lon as f32 * TRAIL_SCALE,
+                    center.y - *lat as f32 * TRAIL_SCALE,
+                )
+            })
+            .collect();
+
+        scene.path(&points, TRAIL_COLOR, false);
+    }
+
+    /// 15°/30° tick marks (with numeric labels every 30°) around a circle of
+    /// radius `MOON_ORBIT_RADIUS`, like a clock face, so the apparent
+    /// east-west wobble of the Moon can be read against a fixed scale.
+    fn draw_orbit_ticks(&self, scene: &mut impl Scene) {
+        let mut deg: i32 = 0;
+        while deg < 360 {
+            let phi = (deg as f64).to_radians();
+            let inner = Self::rphi_to_xy(MOON_ORBIT_RADIUS - ORBIT_TICK_LEN, phi);
+            let outer = Self::rphi_to_xy(MOON_ORBIT_RADIUS + ORBIT_TICK_LEN, phi);
+            scene.line(
+                Point::new(inner.0, inner.1),
+                Point::new(outer.0, outer.1),
+                ORBIT_TICK_COLOR,
+            );
+
+            if deg % 30 == 0 {
+                let label_pos = Self::rphi_to_xy(MOON_ORBIT_RADIUS + ORBIT_TICK_LABEL_OFFSET, phi);
+                scene.text(
+                    Point::new(label_pos.0, label_pos.1),
+                    format!("{}", deg),
+                    ORBIT_TICK_COLOR,
+                    ORBIT_TICK_FONT_SIZE,
+                );
+            }
+
+            deg += 15;
+        }
+    }
+
+    fn draw_moon_orbit(&self, scene: &mut impl Scene) {
+        let mut points = Vec::new();
         let mut phi = 0.0;
         while phi < 2.0 * PI {
             let r = self.r(MOON_ORBIT_RADIUS, phi);
             let (x, y) = Self::rphi_to_xy(r, phi);
-            let old_point = Point::new(x, y);
+            points.push(Point::new(x, y));
             phi += 0.01;
-            let r = self.r(MOON_ORBIT_RADIUS, phi);
-            let (x, y) = Self::rphi_to_xy(r, phi);
-            let new_point = Point::new(x, y);
-            let path = Path::line(old_point, new_point);
-            frame.stroke(&path, Stroke::default().with_color(MOON_COLOR));
         }
+        scene.path(&points, MOON_COLOR, false);
     }
 
-    fn draw_moon(&self, frame: &mut Frame) {
-        frame.with_save(|frame| {
-            let (x, y) = self.moon_pos();
-            frame.translate(Vector::new(x, y));
-            frame.rotate((-self.time * 2.0 * PI) as f32);
+    fn draw_moon(&self, scene: &mut impl Scene) {
+        let (x, y, _) = self.moon_pos();
+        scene.with_transform(Vector::new(x, y), 1.0, 0.0, |scene| {
+            scene.with_transform(
+                Vector::new(0.0, 0.0),
+                1.0,
+                (-self.time * 2.0 * PI) as f32,
+                |scene| {
+                    let indicator_tip = Point::new(INDICATOR_LEN, 0.0);
+                    scene.line(Point::new(0.0, 0.0), indicator_tip, INDICATOR_COLOR);
+                    scene.path(
+                        &[
+                            indicator_tip,
+                            Point::new(
+                                INDICATOR_LEN - INDICATOR_ARROW_SIZE,
+                                INDICATOR_ARROW_SIZE / 2.0,
+                            ),
+                            Point::new(
+                                INDICATOR_LEN - INDICATOR_ARROW_SIZE,
+                                -INDICATOR_ARROW_SIZE / 2.0,
+                            ),
+                            indicator_tip,
+                        ],
+                        INDICATOR_COLOR,
+                        true,
+                    );
 
-            let indicator = Path::line(Point::new(0.0, 0.0), Point::new(INDICATOR_LEN, 0.0));
-            let mut indicator_arrow_head_builder = Builder::new();
-            indicator_arrow_head_builder.move_to(Point::new(INDICATOR_LEN, 0.0));
-            indicator_arrow_head_builder.line_to(Point::new(
-                INDICATOR_LEN - INDICATOR_ARROW_SIZE,
-                INDICATOR_ARROW_SIZE / 2.0,
-            ));
-            indicator_arrow_head_builder.line_to(Point::new(
-                INDICATOR_LEN - INDICATOR_ARROW_SIZE,
-                -INDICATOR_ARROW_SIZE / 2.0,
-            ));
-            indicator_arrow_head_builder.line_to(Point::new(INDICATOR_LEN, 0.0));
-            let indicator_arrow_head = indicator_arrow_head_builder.build();
-
-            frame.stroke(&indicator, Stroke::default().with_color(INDICATOR_COLOR));
-            frame.fill(
-                &indicator_arrow_head,
-                Fill {
-                    color: INDICATOR_COLOR,
-                    ..Default::default()
+                    scene.circle(Point::new(0.0, 0.0), MOON_RADIUS, MOON_COLOR, true);
                 },
             );
 
-            let moon = Path::circle(Point::new(0.0, 0.0), MOON_RADIUS);
-            frame.fill(
-                &moon,
-                Fill {
-                    color: MOON_COLOR,
-                    ..Default::default()
-                },
+            // The sub-observer crosshair isn't rotated with the indicator:
+            // it marks the apparent center of the visible face, which nods
+            // north-south independently of the synodic spin.
+            let offset_y = (MOON_RADIUS as f64 * self.sub_observer_latitude().sin()) as f32;
+            scene.line(
+                Point::new(-CROSSHAIR_SIZE, offset_y),
+                Point::new(CROSSHAIR_SIZE, offset_y),
+                CROSSHAIR_COLOR,
+            );
+            scene.line(
+                Point::new(0.0, offset_y - CROSSHAIR_SIZE),
+                Point::new(0.0, offset_y + CROSSHAIR_SIZE),
+                CROSSHAIR_COLOR,
             );
         });
     }
 
-    fn draw_earth_moon_line(&self, frame: &mut Frame) {
-        let (x, y) = self.moon_pos();
-        let path = Path::line(Point::new(0.0, 0.0), Point::new(x, y));
+    fn draw_earth_moon_line(&self, scene: &mut impl Scene) {
+        let (x, y, _) = self.moon_pos();
+        scene.line(
+            Point::new(0.0, 0.0),
+            Point::new(x, y),
+            EARTH_MOON_LINE_COLOR,
+        );
+    }
+}
+
+/// A tiny recursive-descent evaluator for `+ - * /` and parentheses over
+/// floating point literals, so console commands can take expressions like
+/// `0.0549 * 2` instead of only bare numbers.
+fn eval_expr(input: &str) -> Option<f64> {
+    struct Parser<'a> {
+        chars: std::iter::Peekable<std::str::Chars<'a>>,
+    }
+
+    impl<'a> Parser<'a> {
+        fn skip_ws(&mut self) {
+            while matches!(self.chars.peek(), Some(c) if c.is_whitespace()) {
+                self.chars.next();
+            }
+        }
+
+        fn parse_expr(&mut self) -> Option<f64> {
+            let mut value = self.parse_term()?;
+            loop {
+                self.skip_ws();
+                match self.chars.peek() {
+                    Some('+') => {
+                        self.chars.next();
+                        value += self.parse_term()?;
+                    }
+                    Some('-') => {
+                        self.chars.next();
+                        value -= self.parse_term()?;
+                    }
+                    _ => break,
+                }
+            }
+            Some(value)
+        }
+
+        fn parse_term(&mut self) -> Option<f64> {
+            let mut value = self.parse_factor()?;
+            loop {
+                self.skip_ws();
+                match self.chars.peek() {
+                    Some('*') => {
+                        self.chars.next();
+                        value *= self.parse_factor()?;
+                    }
+                    Some('/') => {
+                        self.chars.next();
+                        value /= self.parse_factor()?;
+                    }
+                    _ => break,
+                }
+            }
+            Some(value)
+        }
+
+        fn parse_factor(&mut self) -> Option<f64> {
+            self.skip_ws();
+            if let Some('(') = self.chars.peek() {
+                self.chars.next();
+                let value = self.parse_expr()?;
+                self.skip_ws();
+                if let Some(')') = self.chars.peek() {
+                    self.chars.next();
+                }
+                return Some(value);
+            }
+            if let Some('-') = self.chars.peek() {
+                self.chars.next();
+                return Some(-self.parse_factor()?);
+            }
+
+            let mut number = String::new();
+            while matches!(self.chars.peek(), Some(c) if c.is_ascii_digit() || *c == '.') {
+                number.push(self.chars.next().unwrap());
+            }
+            number.parse().ok()
+        }
+    }
 
-        frame.stroke(&path, Stroke::default().with_color(EARTH_MOON_LINE_COLOR));
+    let mut parser = Parser {
+        chars: input.trim().chars().peekable(),
+    };
+    let value = parser.parse_expr()?;
+    parser.skip_ws();
+    if parser.chars.next().is_some() {
+        return None;
     }
+    Some(value)
 }
 
 fn main() {
@@ -0,0 +1,83 @@
+//! Wire protocol for the Unix-socket control service: a length-prefixed
+//! JSON message on top of a Unix domain socket under `$XDG_RUNTIME_DIR`.
+//! Shared between the main application (which listens) and the
+//! `libration-ctl` companion binary (which connects and sends one command).
+
+use std::{
+    env,
+    io::{self, Read, Write},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A command understood by the running animation.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WireCommand {
+    SetEccentricity(f64),
+    SetPeriod(f64),
+    Play,
+    Pause,
+    Seek(f64),
+    SetScale(f64),
+    Query,
+}
+
+/// The subset of `Libration`'s state exposed to control clients.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub playing: bool,
+    pub scale: f64,
+    pub time: f64,
+    pub period: f64,
+    pub eccentricity: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum Response {
+    Ack,
+    State(StateSnapshot),
+    Error(String),
+}
+
+/// Path of the control socket: `$XDG_RUNTIME_DIR/libration.sock`, falling
+/// back to a path under `/tmp` if the variable isn't set.
+pub fn socket_path() -> PathBuf {
+    let dir = env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/tmp"));
+    dir.join("libration.sock")
+}
+
+/// Largest length prefix `read_message` will believe. `WireCommand` and
+/// `Response` are both small tagged-union messages; anything claiming to be
+/// bigger than this is either corrupt or hostile, not a real command.
+const MAX_MESSAGE_LEN: usize = 64 * 1024;
+
+/// Reads one length-prefixed JSON message: a 4-byte little-endian length,
+/// followed by that many bytes of JSON.
+pub fn read_message<T: for<'de> Deserialize<'de>>(stream: &mut impl Read) -> io::Result<T> {
+    let mut len_bytes = [0u8; 4];
+    stream.read_exact(&mut len_bytes)?;
+    let len = u32::from_le_bytes(len_bytes) as usize;
+    if len > MAX_MESSAGE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("message length {} exceeds max of {}", len, MAX_MESSAGE_LEN),
+        ));
+    }
+
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf)?;
+
+    serde_json::from_slice(&buf).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Writes one length-prefixed JSON message.
+pub fn write_message<T: Serialize>(stream: &mut impl Write, value: &T) -> io::Result<()> {
+    let payload =
+        serde_json::to_vec(value).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    stream.write_all(&(payload.len() as u32).to_le_bytes())?;
+    stream.write_all(&payload)?;
+    Ok(())
+}